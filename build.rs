@@ -0,0 +1,55 @@
+extern crate regex;
+
+use regex::Regex;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Reads every file in the `patterns/` directory and bakes its `NAME  regex`
+/// lines into `$OUT_DIR/patterns.rs` as a slice of `(bundle, &[(name, regex)])`
+/// tuples. The filename (without extension) is used as the bundle name so that
+/// `Grok::with_named_patterns` can opt into individual bundles.
+fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is not set");
+    let dest = Path::new(&out_dir).join("patterns.rs");
+    let mut out = BufWriter::new(File::create(&dest).expect("could not create patterns.rs"));
+
+    // `NAME  regex` with at least one whitespace separator; blank lines and
+    // lines starting with `#` are comments and get skipped.
+    let line = Regex::new(r"^(\w+)\s+(.*)$").unwrap();
+
+    let mut entries: Vec<_> = fs::read_dir("patterns")
+        .expect("could not read the patterns directory")
+        .map(|e| e.expect("could not read a patterns directory entry").path())
+        .collect();
+    // Keep the generated output stable across builds.
+    entries.sort();
+
+    write!(out, "&[").unwrap();
+    for path in entries {
+        let bundle = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .expect("pattern file has no valid name")
+            .to_owned();
+        write!(out, "({:?}, &[", bundle).unwrap();
+        let content = fs::read_to_string(&path).expect("could not read a pattern file");
+        for raw in content.lines() {
+            let raw = raw.trim();
+            if raw.is_empty() || raw.starts_with('#') {
+                continue;
+            }
+            if let Some(caps) = line.captures(raw) {
+                let name = caps.get(1).unwrap().as_str();
+                let regex = caps.get(2).unwrap().as_str().trim_end();
+                write!(out, "({:?}, {:?}),", name, regex).unwrap();
+            }
+        }
+        write!(out, "]),").unwrap();
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    write!(out, "]").unwrap();
+
+    println!("cargo:rerun-if-changed=patterns");
+}
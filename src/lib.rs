@@ -5,25 +5,77 @@
 #![doc(html_root_url = "https://docs.rs/grok/0.1.0")]
 extern crate regex;
 
-use regex::{Captures, Regex};
+use regex::{Captures, Regex, RegexBuilder};
 use std::collections::BTreeMap;
+use std::collections::btree_map;
 use std::fmt;
 use std::error::Error as StdError;
 
 const MAX_RECURSION: usize = 1024;
 const GROK_PATTERN: &'static str = r"%\{(?P<name>(?P<pattern>[A-z0-9]+)(?::(?P<alias>[A-z0-9_:;/\s\.]+))?)(?:=(?P<definition>(?:(?:[^{}]+|\.+)+)+))?\}";
 
+/// The standard grok pattern bundles baked into the binary at build time, as a
+/// slice of `(bundle_name, &[(pattern_name, regex)])` tuples. Generated from the
+/// files in the `patterns/` directory by `build.rs`.
+const PATTERNS: &'static [(&'static str, &'static [(&'static str, &'static str)])] =
+    include!(concat!(env!("OUT_DIR"), "/patterns.rs"));
+
+/// The declared type of a field, parsed from a `%{PATTERN:name:type}` hint.
+///
+/// Used to remember how a captured field is intended to be interpreted so that
+/// downstream code can coerce it without re-parsing the expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldType {
+    /// `int` / `integer`.
+    Int,
+    /// `float` / `number`.
+    Float,
+    /// `bool` / `boolean`.
+    Bool,
+    /// `string`.
+    Str,
+}
+
+impl FieldType {
+    /// Resolves a type hint token (the part after the last `:` in an alias) into
+    /// a `FieldType`, returning `None` if the token is not a known type.
+    fn from_hint(hint: &str) -> Option<FieldType> {
+        match hint {
+            "int" | "integer" => Some(FieldType::Int),
+            "float" | "number" => Some(FieldType::Float),
+            "bool" | "boolean" => Some(FieldType::Bool),
+            "string" => Some(FieldType::Str),
+            _ => None,
+        }
+    }
+}
+
+/// Splits an alias into its bare field name and an optional declared type.
+///
+/// The type hint is the token following the last `:`; if that token is not one
+/// of the recognized type names the whole alias is taken as the field name so
+/// that aliases which legitimately contain a colon keep working.
+fn split_alias(alias: &str) -> (String, Option<FieldType>) {
+    if let Some(idx) = alias.rfind(':') {
+        if let Some(t) = FieldType::from_hint(&alias[idx + 1..]) {
+            return (alias[..idx].into(), Some(t));
+        }
+    }
+    (alias.into(), None)
+}
+
 /// The `Matches` represent matched results from a `Pattern` against text.
 #[derive(Debug)]
 pub struct Matches<'a> {
     captures: Captures<'a>,
-    alias: &'a BTreeMap<String, String>
+    alias: &'a BTreeMap<String, String>,
+    types: &'a BTreeMap<String, FieldType>,
 }
 
 impl<'a> Matches<'a> {
     /// Instantiates the matches for a pattern after the match.
-    pub fn new(captures: Captures<'a>, alias: &'a BTreeMap<String, String>) -> Self {
-        Matches { captures: captures, alias: alias }
+    pub fn new(captures: Captures<'a>, alias: &'a BTreeMap<String, String>, types: &'a BTreeMap<String, FieldType>) -> Self {
+        Matches { captures: captures, alias: alias, types: types }
     }
 
     /// Gets the value for the name (or) alias if found, `None` otherwise.
@@ -34,6 +86,56 @@ impl<'a> Matches<'a> {
         }
     }
 
+    /// Gets the captured value for the field coerced to an `i64`.
+    ///
+    /// Returns `None` if the field is unknown or did not participate in the
+    /// match, and `Some(Err(..))` if the raw text is not a valid integer.
+    pub fn get_i64(&self, name_or_alias: &str) -> Option<Result<i64, ConvError>> {
+        self.get(name_or_alias)
+            .map(|raw| raw.parse::<i64>().map_err(|_| ConvError::NotAnInteger(raw.into())))
+    }
+
+    /// Gets the captured value for the field coerced to an `f64`.
+    ///
+    /// Returns `None` if the field is unknown or did not participate in the
+    /// match, and `Some(Err(..))` if the raw text is not a valid number.
+    pub fn get_f64(&self, name_or_alias: &str) -> Option<Result<f64, ConvError>> {
+        self.get(name_or_alias)
+            .map(|raw| raw.parse::<f64>().map_err(|_| ConvError::NotAFloat(raw.into())))
+    }
+
+    /// Gets the captured value for the field coerced to a `bool`.
+    ///
+    /// Accepts `true`/`false`/`1`/`0` case-insensitively. Returns `None` if the
+    /// field is unknown or did not participate in the match, and `Some(Err(..))`
+    /// if the raw text is not a recognized boolean.
+    pub fn get_bool(&self, name_or_alias: &str) -> Option<Result<bool, ConvError>> {
+        self.get(name_or_alias).map(|raw| match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(ConvError::NotABool(raw.into())),
+        })
+    }
+
+    /// Gets the declared type for the field if one was given as a `:type` hint
+    /// in the pattern (e.g. `%{NUMBER:bytes:int}`), `None` otherwise.
+    pub fn field_type(&self, name_or_alias: &str) -> Option<&FieldType> {
+        self.types.get(name_or_alias)
+    }
+
+    /// Returns an iterator over all captured fields as `(name, value)` pairs.
+    ///
+    /// The name is the alias (or pattern name) the field was compiled under and
+    /// the value is the captured text; fields that did not participate in the
+    /// match yield an empty string. This is handy for turning a matched line into
+    /// a generic key/value record without knowing the field names up front.
+    pub fn iter(&'a self) -> MatchesIter<'a> {
+        MatchesIter {
+            captures: &self.captures,
+            alias: self.alias.iter(),
+        }
+    }
+
     /// Returns the number of matches.
     pub fn len(&self) -> usize {
         self.captures.len() - 1
@@ -43,28 +145,282 @@ impl<'a> Matches<'a> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
-} 
+}
+
+impl<'a> IntoIterator for &'a Matches<'a> {
+    type Item = (&'a str, &'a str);
+    type IntoIter = MatchesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the captured fields of a `Matches`, yielding `(name, value)`
+/// pairs for every field in the compiled pattern. Created by [`Matches::iter`].
+#[derive(Debug)]
+pub struct MatchesIter<'a> {
+    captures: &'a Captures<'a>,
+    alias: btree_map::Iter<'a, String, String>,
+}
+
+impl<'a> Iterator for MatchesIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.alias.next().map(|(name, real)| {
+            let value = self.captures.name(real).map_or("", |m| m.as_str());
+            (name.as_str(), value)
+        })
+    }
+}
 
 /// The `Pattern` represents a compiled regex, ready to be matched against arbitrary text.
 #[derive(Debug)]
 pub struct Pattern {
     regex: Regex,
     alias: BTreeMap<String, String>,
+    types: BTreeMap<String, FieldType>,
+    max_input_len: Option<usize>,
 }
 
 impl Pattern {
     /// Creates a new pattern from a raw regex string and an alias map to identify the
-    /// fields properly.
-    pub fn new(regex: &str, alias: BTreeMap<String, String>) -> Result<Self, Error> {
-        match Regex::new(regex) {
-            Ok(r) => Ok (Pattern { regex: r, alias: alias }),
+    /// fields properly, using the default regex flags.
+    pub fn new(regex: &str, alias: BTreeMap<String, String>, types: BTreeMap<String, FieldType>) -> Result<Self, Error> {
+        Pattern::compiled(regex, alias, types, &CompileOptions::default())
+    }
+
+    /// Creates a new pattern, applying the regex flags carried by `options`
+    /// through `RegexBuilder` so callers can opt into multi-line, dotall and
+    /// friends.
+    fn compiled(regex: &str, alias: BTreeMap<String, String>, types: BTreeMap<String, FieldType>, options: &CompileOptions) -> Result<Self, Error> {
+        let mut builder = RegexBuilder::new(regex);
+        builder
+            .multi_line(options.multi_line)
+            .dot_matches_new_line(options.dot_matches_new_line)
+            .case_insensitive(options.case_insensitive)
+            .ignore_whitespace(options.ignore_whitespace);
+        // Bound how big the compiled program is allowed to grow so that a
+        // pathological expression is rejected at compile time rather than
+        // blowing up the engine later.
+        if let Some(limit) = options.size_limit {
+            builder.size_limit(limit);
+        }
+        if let Some(limit) = options.dfa_size_limit {
+            builder.dfa_size_limit(limit);
+        }
+        match builder.build() {
+            Ok(r) => Ok (Pattern { regex: r, alias: alias, types: types, max_input_len: options.max_input_len }),
             Err(_) => Err(Error::RegexCompilationFailed(regex.into())),
         }
     }
 
     /// Matches this compiled `Pattern` against the text and returns the matches.
     pub fn match_against<'a>(&'a self, text: &'a str) -> Option<Matches<'a>> {
-        self.regex.captures(text).map(|cap| Matches::new(cap, &self.alias))
+        self.regex.captures(text).map(|cap| Matches::new(cap, &self.alias, &self.types))
+    }
+
+    /// Sets the maximum input length accepted by [`try_match_against`](Pattern::try_match_against).
+    ///
+    /// Inputs longer than this are rejected with [`Error::MatchFailed`] instead
+    /// of being handed to the engine, guarding against adversarial input that
+    /// could make matching pathologically expensive.
+    pub fn set_max_input_len(&mut self, max: usize) {
+        self.max_input_len = Some(max);
+    }
+
+    /// Like [`match_against`](Pattern::match_against) but defensive: it enforces
+    /// the configured [`max_input_len`](Pattern::set_max_input_len) guard and
+    /// returns a structured [`Error::MatchFailed`] rather than risking a runtime
+    /// blow-up on adversarial input.
+    pub fn try_match_against<'a>(&'a self, text: &'a str) -> Result<Option<Matches<'a>>, Error> {
+        if let Some(max) = self.max_input_len {
+            if text.len() > max {
+                return Err(Error::MatchFailed(format!(
+                    "input of {} bytes exceeds the configured limit of {}; try simplifying the expression or shortening the input",
+                    text.len(),
+                    max
+                )));
+            }
+        }
+        Ok(self.match_against(text))
+    }
+
+    /// Replaces the first occurrence of this pattern in `text` with the rendered
+    /// `template`, copying the non-matching spans verbatim.
+    ///
+    /// The template references captured fields by alias using `${name}` or
+    /// `$name`, and `$$` emits a literal dollar sign. A reference to a field the
+    /// pattern does not capture is an [`Error::TemplateFieldNotFound`] rather than
+    /// a silent empty expansion. Returns `text` unchanged when nothing matches.
+    pub fn replace(&self, text: &str, template: &str) -> Result<String, Error> {
+        self.replace_limited(text, template, 1)
+    }
+
+    /// Like [`replace`](Pattern::replace) but substitutes every non-overlapping
+    /// occurrence of the pattern.
+    pub fn replace_all(&self, text: &str, template: &str) -> Result<String, Error> {
+        self.replace_limited(text, template, 0)
+    }
+
+    /// Renders up to `limit` matches (0 meaning "all") into `text`, stitching the
+    /// untouched spans between matches back in.
+    fn replace_limited(&self, text: &str, template: &str, limit: usize) -> Result<String, Error> {
+        let mut out = String::new();
+        let mut last = 0;
+        for (count, caps) in self.regex.captures_iter(text).enumerate() {
+            if limit != 0 && count >= limit {
+                break;
+            }
+            let whole = caps.get(0).expect("capture group zero always exists");
+            out.push_str(&text[last..whole.start()]);
+            self.render_template(template, &caps, &mut out)?;
+            last = whole.end();
+        }
+        out.push_str(&text[last..]);
+        Ok(out)
+    }
+
+    /// Expands the `${name}` / `$name` tokens of `template` against a single set
+    /// of captures, appending the result to `out`. `$$` escapes a literal dollar.
+    fn render_template(&self, template: &str, caps: &Captures, out: &mut String) -> Result<(), Error> {
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                out.push(c);
+                continue;
+            }
+            match chars.peek() {
+                Some(&'$') => {
+                    chars.next();
+                    out.push('$');
+                }
+                Some(&'{') => {
+                    chars.next();
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => name.push(ch),
+                            None => return Err(Error::InvalidTemplate(template.into())),
+                        }
+                    }
+                    if name.is_empty() {
+                        return Err(Error::InvalidTemplate(template.into()));
+                    }
+                    self.push_field(&name, caps, out)?;
+                }
+                Some(&ch) if ch.is_ascii_alphanumeric() || ch == '_' => {
+                    let mut name = String::new();
+                    while let Some(&ch) = chars.peek() {
+                        if ch.is_ascii_alphanumeric() || ch == '_' {
+                            name.push(ch);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    self.push_field(&name, caps, out)?;
+                }
+                // A lone `$` that is not the start of a reference is kept as-is.
+                _ => out.push('$'),
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves a field name through the `alias` → internal-group indirection and
+    /// appends its captured value to `out`, erroring if the field is unknown.
+    fn push_field(&self, name: &str, caps: &Captures, out: &mut String) -> Result<(), Error> {
+        match self.alias.get(name) {
+            Some(real) => {
+                out.push_str(caps.name(real).map_or("", |m| m.as_str()));
+                Ok(())
+            }
+            None => Err(Error::TemplateFieldNotFound(name.into())),
+        }
+    }
+}
+
+/// Flags controlling how a pattern's final regex is built.
+///
+/// Defaults to all flags off, which reproduces the behaviour of plain
+/// [`Grok::compile`]. Pass one to [`Grok::compile_with_options`] to opt into
+/// multi-line anchoring, letting `.` span newlines, and so on — useful for
+/// grok expressions that must match across embedded newlines in a single
+/// multi-line log event.
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptions {
+    with_alias_only: bool,
+    multi_line: bool,
+    dot_matches_new_line: bool,
+    case_insensitive: bool,
+    ignore_whitespace: bool,
+    size_limit: Option<usize>,
+    dfa_size_limit: Option<usize>,
+    max_input_len: Option<usize>,
+}
+
+impl CompileOptions {
+    /// Creates a new set of options with every flag disabled.
+    pub fn new() -> Self {
+        CompileOptions::default()
+    }
+
+    /// When true, only fields given an explicit alias are captured (mirrors the
+    /// `with_alias_only` argument of [`Grok::compile`]).
+    pub fn with_alias_only(mut self, yes: bool) -> Self {
+        self.with_alias_only = yes;
+        self
+    }
+
+    /// When true, `^` and `$` match at line boundaries instead of only at the
+    /// start and end of the whole input.
+    pub fn multi_line(mut self, yes: bool) -> Self {
+        self.multi_line = yes;
+        self
+    }
+
+    /// When true, `.` also matches newlines (dotall mode).
+    pub fn dot_matches_new_line(mut self, yes: bool) -> Self {
+        self.dot_matches_new_line = yes;
+        self
+    }
+
+    /// When true, matching is case-insensitive.
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.case_insensitive = yes;
+        self
+    }
+
+    /// When true, insignificant whitespace in the pattern is ignored (verbose
+    /// mode).
+    pub fn ignore_whitespace(mut self, yes: bool) -> Self {
+        self.ignore_whitespace = yes;
+        self
+    }
+
+    /// Caps the size (in bytes) of the compiled regex program, rejecting
+    /// pathologically large expressions at compile time.
+    pub fn size_limit(mut self, bytes: usize) -> Self {
+        self.size_limit = Some(bytes);
+        self
+    }
+
+    /// Caps the size (in bytes) of the cached DFA the engine may build while
+    /// matching, bounding its memory use on adversarial input.
+    pub fn dfa_size_limit(mut self, bytes: usize) -> Self {
+        self.dfa_size_limit = Some(bytes);
+        self
+    }
+
+    /// Sets the maximum input length honoured by
+    /// [`Pattern::try_match_against`]; longer inputs fail with
+    /// [`Error::MatchFailed`] instead of being matched.
+    pub fn max_input_len(mut self, max: usize) -> Self {
+        self.max_input_len = Some(max);
+        self
     }
 }
 
@@ -82,15 +438,55 @@ impl Grok {
         }
     }
 
+    /// Creates a new `Grok` instance pre-loaded with the full set of built-in
+    /// pattern bundles (`grok-patterns`, `linux-syslog`, `httpd`, `java` and
+    /// `aws`), so that expressions like `%{COMMONMAC}` compile out of the
+    /// box without any manual `insert_definition` calls.
+    pub fn with_patterns() -> Self {
+        let mut grok = Grok::empty();
+        for &(_, patterns) in PATTERNS {
+            for &(name, definition) in patterns {
+                grok.insert_definition(name, definition);
+            }
+        }
+        grok
+    }
+
+    /// Creates a new `Grok` instance pre-loaded with only the named built-in
+    /// bundles, e.g. `Grok::with_named_patterns(&["grok-patterns", "aws"])`.
+    /// Unknown bundle names are silently ignored.
+    pub fn with_named_patterns(names: &[&str]) -> Self {
+        let mut grok = Grok::empty();
+        for &(bundle, patterns) in PATTERNS {
+            if names.contains(&bundle) {
+                for &(name, definition) in patterns {
+                    grok.insert_definition(name, definition);
+                }
+            }
+        }
+        grok
+    }
+
     /// Inserts a custom pattern.
     pub fn insert_definition<S: Into<String>>(&mut self, name: S, pattern: S) {
         self.definitions.insert(name.into(), pattern.into());
     }
 
-    /// Compiles the given pattern, making it ready for matching.
+    /// Compiles the given pattern with default regex flags, making it ready for
+    /// matching. A thin wrapper over [`compile_with_options`](Grok::compile_with_options).
     pub fn compile(&mut self, pattern: &str, with_alias_only: bool) -> Result<Pattern, Error> {
+        let options = CompileOptions::default().with_alias_only(with_alias_only);
+        self.compile_with_options(pattern, &options)
+    }
+
+    /// Compiles the given pattern, applying the regex flags in `options` (e.g.
+    /// `multi_line` or `dot_matches_new_line`) to the resulting engine so a
+    /// single expression can span embedded newlines in multi-line log events.
+    pub fn compile_with_options(&mut self, pattern: &str, options: &CompileOptions) -> Result<Pattern, Error> {
+        let with_alias_only = options.with_alias_only;
         let mut named_regex = String::from(pattern);
         let mut alias: BTreeMap<String, String> = BTreeMap::new();
+        let mut types: BTreeMap<String, FieldType> = BTreeMap::new();
 
         let mut index = 0;
         let mut iteration_left = MAX_RECURSION;
@@ -147,10 +543,16 @@ impl Grok {
 
                     // If an alias is specified by the user use that one to match the name<index>
                     // conversion, oterhwise just use the name of the pattern definition directly.
-                    alias.insert(match m.name("alias") {
-                        Some(a) => a.as_str().into(),
-                        None => name.clone(),
-                    }, format!("name{}", index));
+                    // A trailing `:type` hint on the alias (e.g. `bytes:int`) is peeled off into
+                    // the types map so the bare field name keeps resolving through `alias`.
+                    let (field, declared) = match m.name("alias") {
+                        Some(a) => split_alias(a.as_str()),
+                        None => (name.clone(), None),
+                    };
+                    if let Some(field_type) = declared {
+                        types.insert(field.clone(), field_type);
+                    }
+                    alias.insert(field, format!("name{}", index));
 
 
                     // Finally, look for the original %{...} style pattern and replace it
@@ -167,14 +569,107 @@ impl Grok {
         if named_regex.is_empty() {
             Err(Error::CompiledPatternIsEmpty(pattern.into()))
         } else {
-            Pattern::new(&named_regex, alias)
+            Pattern::compiled(&named_regex, alias, types, options)
         }
     }
 }
 
 impl Default for Grok {
     fn default() -> Grok {
-        Grok::empty()
+        Grok::with_patterns()
+    }
+}
+
+/// A single hit produced by matching a `GrokSet` against an input line,
+/// carrying the registered index and label of the matching pattern alongside
+/// its extracted `Matches`.
+#[derive(Debug)]
+pub struct SetMatch<'a> {
+    /// The registration index of the matching pattern within the set.
+    pub index: usize,
+    /// The label the pattern was registered under.
+    pub label: &'a str,
+    /// The fields extracted by the matching pattern.
+    pub matches: Matches<'a>,
+}
+
+/// Accumulates labelled `Pattern`s before they are frozen into a `GrokSet`.
+///
+/// Created with [`GrokSet::builder`]; add patterns with [`add`](GrokSetBuilder::add)
+/// and finalize with [`build`](GrokSetBuilder::build).
+#[derive(Debug, Default)]
+pub struct GrokSetBuilder {
+    patterns: Vec<(String, Pattern)>,
+}
+
+impl GrokSetBuilder {
+    /// Registers a compiled `Pattern` under the given label. Patterns keep the
+    /// order in which they were added, which is the order `first_match` honours.
+    pub fn add<S: Into<String>>(&mut self, label: S, pattern: Pattern) -> &mut Self {
+        self.patterns.push((label.into(), pattern));
+        self
+    }
+
+    /// Freezes the accumulated patterns into an immutable `GrokSet`.
+    pub fn build(self) -> GrokSet {
+        GrokSet { patterns: self.patterns }
+    }
+}
+
+/// Holds many compiled `Pattern`s and reports which of them match a given line.
+///
+/// This is the grok analogue of globset's `GlobSet`: a whole catalogue of log
+/// formats can be matched against an input through one call so the caller
+/// learns which format(s) a line belongs to. The patterns are not merged into a
+/// single engine; each one runs its own regex against the input, in
+/// registration order. Build one through [`GrokSet::builder`].
+#[derive(Debug)]
+pub struct GrokSet {
+    patterns: Vec<(String, Pattern)>,
+}
+
+impl GrokSet {
+    /// Starts building a new `GrokSet`.
+    pub fn builder() -> GrokSetBuilder {
+        GrokSetBuilder::default()
+    }
+
+    /// Matches `text` against every pattern in the set and returns a `SetMatch`
+    /// for each one that hit, in registration order.
+    pub fn matches<'a>(&'a self, text: &'a str) -> Vec<SetMatch<'a>> {
+        self.hits(text).collect()
+    }
+
+    /// Returns the earliest-registered pattern that matches `text`, or `None` if
+    /// none do. This covers the common "dispatch on log format" case without
+    /// running every remaining pattern once a match is found.
+    pub fn first_match<'a>(&'a self, text: &'a str) -> Option<SetMatch<'a>> {
+        self.hits(text).next()
+    }
+
+    /// Produces a lazy `SetMatch` for each pattern that hits `text`, in
+    /// registration order. Shared by `matches` and `first_match`.
+    fn hits<'a>(&'a self, text: &'a str) -> impl Iterator<Item = SetMatch<'a>> {
+        self.patterns
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, &(ref label, ref pattern))| {
+                pattern.match_against(text).map(|matches| SetMatch {
+                    index: index,
+                    label: label.as_str(),
+                    matches: matches,
+                })
+            })
+    }
+
+    /// Returns the number of patterns in the set.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns true if the set holds no patterns, false otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
     }
 }
 
@@ -191,6 +686,12 @@ pub enum Error {
     RegexCompilationFailed(String),
     /// Something is messed up during the compilation phase.
     GenericCompilationFailure(String),
+    /// A replacement template is malformed, e.g. an unterminated `${...}`.
+    InvalidTemplate(String),
+    /// A replacement template references a field the pattern does not capture.
+    TemplateFieldNotFound(String),
+    /// Matching failed at runtime, e.g. the input tripped a configured guard.
+    MatchFailed(String),
     /// Hints that destructuring should not be exhaustive.
     ///
     /// This enum may grow additional variants, so this makes sure clients
@@ -208,6 +709,9 @@ impl StdError for Error {
             Error::DefinitionNotFound(_) => "pattern definition not found while compiling",
             Error::RegexCompilationFailed(_) => "regex compilation in the engine failed",
             Error::GenericCompilationFailure(_) => "something happened during the compilation phase",
+            Error::InvalidTemplate(_) => "replacement template is malformed",
+            Error::TemplateFieldNotFound(_) => "replacement template references an unknown field",
+            Error::MatchFailed(_) => "matching failed at runtime",
             Error::__Nonexhaustive => unreachable!(),
         }
     }
@@ -230,16 +734,218 @@ impl fmt::Display for Error {
                 write!(f, "The given regex \"{}\" failed compilation in the underlying engine", r),
             Error::GenericCompilationFailure(ref d) =>
                 write!(f, "Something unexpected happened during the compilation phase: \"{}\"", d),
+            Error::InvalidTemplate(ref t) =>
+                write!(f, "The replacement template is malformed: \"{}\"", t),
+            Error::TemplateFieldNotFound(ref n) =>
+                write!(f, "The replacement template references the unknown field \"{}\"", n),
+            Error::MatchFailed(ref m) =>
+                write!(f, "Matching failed at runtime: {}", m),
             Error::__Nonexhaustive => unreachable!(),
         }
     }
 }
 
+/// An error that occurred while coercing a captured field to a typed value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConvError {
+    /// The raw text could not be parsed as an integer.
+    NotAnInteger(String),
+    /// The raw text could not be parsed as a floating point number.
+    NotAFloat(String),
+    /// The raw text is not a recognized boolean (`true`/`false`/`1`/`0`).
+    NotABool(String),
+    /// Hints that destructuring should not be exhaustive.
+    ///
+    /// This enum may grow additional variants, so this makes sure clients
+    /// don't count on exhaustive matching. (Otherwise, adding a new variant
+    /// could break existing code.)
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+impl StdError for ConvError {
+    fn description(&self) -> &str {
+        match *self {
+            ConvError::NotAnInteger(_) => "captured value is not a valid integer",
+            ConvError::NotAFloat(_) => "captured value is not a valid float",
+            ConvError::NotABool(_) => "captured value is not a valid boolean",
+            ConvError::__Nonexhaustive => unreachable!(),
+        }
+    }
+
+    fn cause(&self) -> Option<&StdError> {
+        None
+    }
+}
+
+impl fmt::Display for ConvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConvError::NotAnInteger(ref v) =>
+                write!(f, "The captured value \"{}\" could not be parsed as an integer", v),
+            ConvError::NotAFloat(ref v) =>
+                write!(f, "The captured value \"{}\" could not be parsed as a float", v),
+            ConvError::NotABool(ref v) =>
+                write!(f, "The captured value \"{}\" could not be parsed as a boolean", v),
+            ConvError::__Nonexhaustive => unreachable!(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_loaded_default_patterns() {
+        let mut grok = Grok::default();
+        let pattern = grok.compile("%{COMMONMAC}", false).expect("Error while compiling!");
+
+        let matches = pattern.match_against("5E:FF:56:A2:AF:15").expect("No matches found!");
+        assert_eq!("5E:FF:56:A2:AF:15", matches.get("COMMONMAC").unwrap());
+    }
+
+    #[test]
+    fn test_loaded_named_patterns() {
+        let mut grok = Grok::with_named_patterns(&["grok-patterns"]);
+        let pattern = grok.compile("%{USERNAME}", false).expect("Error while compiling!");
+
+        let matches = pattern.match_against("root").expect("No matches found!");
+        assert_eq!("root", matches.get("USERNAME").unwrap());
+    }
+
+    #[test]
+    fn test_typed_field_extraction() {
+        let mut grok = Grok::default();
+        let pattern = grok
+            .compile("%{NUMBER:bytes:int} %{NUMBER:ratio:float} %{WORD:ok:bool}", false)
+            .expect("Error while compiling!");
+
+        let matches = pattern.match_against("42 3.5 TRUE").expect("No matches found!");
+        // The bare field name still resolves as a string.
+        assert_eq!("42", matches.get("bytes").unwrap());
+        assert_eq!(42, matches.get_i64("bytes").unwrap().unwrap());
+        assert_eq!(3.5, matches.get_f64("ratio").unwrap().unwrap());
+        assert_eq!(true, matches.get_bool("ok").unwrap().unwrap());
+        assert_eq!(Some(&FieldType::Int), matches.field_type("bytes"));
+        assert!(matches.get_i64("ratio").unwrap().is_err());
+        assert_eq!(None, matches.get_i64("missing"));
+    }
+
+    #[test]
+    fn test_iterate_over_matches() {
+        let mut grok = Grok::default();
+        let pattern = grok
+            .compile("%{USERNAME:usr} %{USERNAME:host}", false)
+            .expect("Error while compiling!");
+
+        let matches = pattern.match_against("root localhost").expect("No matches found!");
+        let collected: Vec<(&str, &str)> = matches.iter().collect();
+        assert_eq!(vec![("host", "localhost"), ("usr", "root")], collected);
+    }
+
+    #[test]
+    fn test_grok_set_matches() {
+        let mut grok = Grok::default();
+        let mac = grok.compile("%{COMMONMAC:mac}", false).expect("Error while compiling!");
+        let ip = grok.compile("%{IPV4:ip}", false).expect("Error while compiling!");
+
+        let mut builder = GrokSet::builder();
+        builder.add("mac", mac).add("ip", ip);
+        let set = builder.build();
+        assert_eq!(2, set.len());
+
+        let hits = set.matches("00:1b:63:84:45:e6");
+        assert_eq!(1, hits.len());
+        assert_eq!(0, hits[0].index);
+        assert_eq!("mac", hits[0].label);
+        assert_eq!("00:1b:63:84:45:e6", hits[0].matches.get("mac").unwrap());
+
+        let first = set.first_match("127.0.0.1").expect("No matches found!");
+        assert_eq!("ip", first.label);
+        assert_eq!("127.0.0.1", first.matches.get("ip").unwrap());
+
+        assert!(set.matches("not a log line").is_empty());
+    }
+
+    #[test]
+    fn test_template_replace() {
+        let mut grok = Grok::default();
+        let pattern = grok
+            .compile("%{USERNAME:usr}@%{HOSTNAME:host}", false)
+            .expect("Error while compiling!");
+
+        let replaced = pattern
+            .replace("login root@localhost now", "user=${usr} host=$host")
+            .expect("Error while replacing!");
+        assert_eq!("login user=root host=localhost now", replaced);
+
+        // `$$` escapes a literal dollar sign.
+        let escaped = pattern
+            .replace("root@localhost", "$$${usr}")
+            .expect("Error while replacing!");
+        assert_eq!("$root", escaped);
+
+        // replace_all rewrites every occurrence.
+        let all = pattern
+            .replace_all("a@b c@d", "${host}")
+            .expect("Error while replacing!");
+        assert_eq!("b d", all);
+
+        // Unknown field references are a clear error, not a silent blank.
+        assert_eq!(
+            Err(Error::TemplateFieldNotFound("nope".into())),
+            pattern.replace("root@localhost", "${nope}")
+        );
+    }
+
+    #[test]
+    fn test_compile_with_options() {
+        let mut grok = Grok::default();
+
+        // Without dotall, GREEDYDATA (`.*`) stops at the newline.
+        let plain = grok.compile("%{GREEDYDATA:body}", false).expect("Error while compiling!");
+        let matches = plain.match_against("first\nsecond").expect("No matches found!");
+        assert_eq!("first", matches.get("body").unwrap());
+
+        // With dot_matches_new_line the same expression spans the newline.
+        let options = CompileOptions::new().dot_matches_new_line(true);
+        let dotall = grok
+            .compile_with_options("%{GREEDYDATA:body}", &options)
+            .expect("Error while compiling!");
+        let matches = dotall.match_against("first\nsecond").expect("No matches found!");
+        assert_eq!("first\nsecond", matches.get("body").unwrap());
+
+        // Case-insensitive matching honours the flag: DAY only spells out
+        // mixed-case names, so "MONDAY" matches only with the flag set.
+        let ci = grok
+            .compile_with_options("%{DAY:d}", &CompileOptions::new().case_insensitive(true))
+            .expect("Error while compiling!");
+        assert_eq!("MONDAY", ci.match_against("MONDAY").unwrap().get("d").unwrap());
+    }
+
+    #[test]
+    fn test_try_match_against_guard() {
+        let mut grok = Grok::default();
+        let pattern = grok
+            .compile_with_options("%{GREEDYDATA:body}", &CompileOptions::new().max_input_len(8))
+            .expect("Error while compiling!");
+
+        // Short input matches as usual.
+        let matches = pattern
+            .try_match_against("short")
+            .expect("Matching guard tripped unexpectedly!")
+            .expect("No matches found!");
+        assert_eq!("short", matches.get("body").unwrap());
+
+        // Oversized input is rejected with a structured error.
+        match pattern.try_match_against("this input is far too long") {
+            Err(Error::MatchFailed(_)) => {}
+            other => panic!("expected MatchFailed, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_simple_anonymous_pattern() {
         let mut grok = Grok::default();